@@ -0,0 +1,303 @@
+/* LBG/ELBG vector-quantization clustering.
+
+Classic LBG (Linde-Buzo-Gray) starts with a single centroid at the mean of
+all points, then repeatedly splits centroids into two perturbed copies
+(centroid +/- epsilon) until there are K centroids, running Lloyd iterations
+after every split. Each round splits every centroid, except that the last
+round splits only as many as are needed to land exactly on K. ELBG adds an enhancement pass on top: low-utility cells
+(far below average distortion) are migrated next to high-utility cells (far
+above average distortion), splitting the overloaded cell; a migration is
+kept only if it strictly lowers the global distortion, otherwise it is
+rolled back.
+
+References:
+https://en.wikipedia.org/wiki/Linde%E2%80%93Buzo%E2%80%93Gray_algorithm */
+
+use crate::compute_mean;
+use std::collections::HashMap;
+
+type DistMetric = fn(&Vec<f64>, &Vec<f64>) -> f64;
+
+const SPLIT_EPSILON: f64 = 1e-4;
+const MAX_LLOYD_ITERS: usize = 100;
+const LOCAL_LLOYD_ITERS: usize = 20;
+const MAX_ENHANCEMENT_ROUNDS: usize = 10;
+const LOW_UTILITY_FACTOR: f64 = 0.5;
+const HIGH_UTILITY_FACTOR: f64 = 1.5;
+
+fn mean_point(points: &[Vec<f64>]) -> Vec<f64> {
+    let dims = points[0].len();
+    let mut mean = vec![0.0; dims];
+    for (d, slot) in mean.iter_mut().enumerate() {
+        let column: Vec<f64> = points.iter().map(|p| p[d]).collect();
+        *slot = compute_mean(&column);
+    }
+    mean
+}
+
+fn perturb(centroid: &[f64], sign: f64) -> Vec<f64> {
+    centroid.iter().map(|c| c + sign * SPLIT_EPSILON).collect()
+}
+
+fn nearest_centroid(point: &Vec<f64>, centroids: &[Vec<f64>], dist_metric: DistMetric) -> usize {
+    let mut best = 0;
+    let mut best_dist = f64::INFINITY;
+    for (i, centroid) in centroids.iter().enumerate() {
+        let d = dist_metric(point, centroid);
+        if d < best_dist {
+            best_dist = d;
+            best = i;
+        }
+    }
+    best
+}
+
+fn assign(points: &[Vec<f64>], centroids: &[Vec<f64>], dist_metric: DistMetric) -> Vec<usize> {
+    points
+        .iter()
+        .map(|p| nearest_centroid(p, centroids, dist_metric))
+        .collect()
+}
+
+// Sum of squared distances from every point to its assigned centroid.
+fn total_distortion(
+    points: &[Vec<f64>],
+    centroids: &[Vec<f64>],
+    assignments: &[usize],
+    dist_metric: DistMetric,
+) -> f64 {
+    points
+        .iter()
+        .zip(assignments)
+        .map(|(p, &a)| dist_metric(p, &centroids[a]).powi(2))
+        .sum()
+}
+
+fn per_cell_distortion(
+    points: &[Vec<f64>],
+    centroids: &[Vec<f64>],
+    assignments: &[usize],
+    dist_metric: DistMetric,
+) -> Vec<f64> {
+    let mut distortion = vec![0.0; centroids.len()];
+    for (p, &a) in points.iter().zip(assignments) {
+        distortion[a] += dist_metric(p, &centroids[a]).powi(2);
+    }
+    distortion
+}
+
+// Runs Lloyd's algorithm (assign, recompute, repeat) starting from
+// `centroids` until total distortion stops decreasing or `max_iters` is
+// reached. A cluster that ends up with no members keeps its previous
+// centroid in place rather than collapsing to the origin.
+fn lloyd_iterate(
+    points: &[Vec<f64>],
+    mut centroids: Vec<Vec<f64>>,
+    dist_metric: DistMetric,
+    max_iters: usize,
+) -> (Vec<Vec<f64>>, Vec<usize>) {
+    let mut assignments = assign(points, &centroids, dist_metric);
+    let mut prev_distortion = f64::INFINITY;
+
+    for _ in 0..max_iters {
+        for (i, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<Vec<f64>> = points
+                .iter()
+                .zip(&assignments)
+                .filter(|(_, &a)| a == i)
+                .map(|(p, _)| p.clone())
+                .collect();
+            if !members.is_empty() {
+                *centroid = mean_point(&members);
+            }
+        }
+        assignments = assign(points, &centroids, dist_metric);
+        let distortion = total_distortion(points, &centroids, &assignments, dist_metric);
+        if distortion >= prev_distortion {
+            break;
+        }
+        prev_distortion = distortion;
+    }
+
+    (centroids, assignments)
+}
+
+// Identifies low- and high-utility cells and tries migrating a low-utility
+// centroid next to a high-utility one, splitting the overloaded cell and
+// re-running local Lloyd updates. A migration is kept only if it strictly
+// lowers the global distortion.
+fn enhance(
+    points: &[Vec<f64>],
+    mut centroids: Vec<Vec<f64>>,
+    mut assignments: Vec<usize>,
+    dist_metric: DistMetric,
+) -> (Vec<Vec<f64>>, Vec<usize>) {
+    let mut distortion = total_distortion(points, &centroids, &assignments, dist_metric);
+
+    for _ in 0..MAX_ENHANCEMENT_ROUNDS {
+        let cell_distortion = per_cell_distortion(points, &centroids, &assignments, dist_metric);
+        let average = compute_mean(&cell_distortion);
+
+        let low_utility: Vec<usize> = (0..centroids.len())
+            .filter(|&i| cell_distortion[i] < LOW_UTILITY_FACTOR * average)
+            .collect();
+        let high_utility: Vec<usize> = (0..centroids.len())
+            .filter(|&i| cell_distortion[i] > HIGH_UTILITY_FACTOR * average)
+            .collect();
+        if low_utility.is_empty() || high_utility.is_empty() {
+            break;
+        }
+
+        let mut migrated = false;
+        for &low in &low_utility {
+            for &high in &high_utility {
+                if low == high {
+                    continue;
+                }
+                let mut candidate = centroids.clone();
+                candidate[low] = perturb(&centroids[high], 1.0);
+                candidate[high] = perturb(&centroids[high], -1.0);
+
+                let (new_centroids, new_assignments) =
+                    lloyd_iterate(points, candidate, dist_metric, LOCAL_LLOYD_ITERS);
+                let new_distortion =
+                    total_distortion(points, &new_centroids, &new_assignments, dist_metric);
+
+                if new_distortion < distortion {
+                    centroids = new_centroids;
+                    assignments = new_assignments;
+                    distortion = new_distortion;
+                    migrated = true;
+                    break;
+                }
+            }
+            if migrated {
+                break;
+            }
+        }
+
+        if !migrated {
+            break;
+        }
+    }
+
+    (centroids, assignments)
+}
+
+fn labels_to_clusters(
+    points: &[Vec<f64>],
+    assignments: &[usize],
+    num_centroids: usize,
+) -> HashMap<i64, Vec<Vec<f64>>> {
+    let mut clusters: HashMap<i64, Vec<Vec<f64>>> = HashMap::with_capacity(num_centroids);
+    for label in 0..num_centroids {
+        clusters.insert(label as i64, Vec::new());
+    }
+    for (point, &label) in points.iter().zip(assignments) {
+        clusters.get_mut(&(label as i64)).unwrap().push(point.clone());
+    }
+    clusters
+}
+
+// Clusters `points` into `k` groups via LBG splitting followed by an ELBG
+// enhancement pass, returning labeled clusters ready for
+// `compute_silhouette_score`.
+pub fn cluster(
+    points: &Vec<Vec<f64>>,
+    k: usize,
+    dist_metric: DistMetric,
+) -> HashMap<i64, Vec<Vec<f64>>> {
+    assert!(!points.is_empty(), "Cannot cluster an empty dataset.");
+    assert!(k >= 1, "k must be at least 1.");
+
+    let mut centroids = vec![mean_point(points)];
+
+    while centroids.len() < k {
+        let splits = (k - centroids.len()).min(centroids.len());
+        let mut next_centroids = Vec::with_capacity(centroids.len() + splits);
+        for (i, centroid) in centroids.iter().enumerate() {
+            if i < splits {
+                next_centroids.push(perturb(centroid, 1.0));
+                next_centroids.push(perturb(centroid, -1.0));
+            } else {
+                next_centroids.push(centroid.clone());
+            }
+        }
+        centroids = next_centroids;
+
+        let (new_centroids, _) = lloyd_iterate(points, centroids, dist_metric, MAX_LLOYD_ITERS);
+        centroids = new_centroids;
+    }
+
+    let assignments = assign(points, &centroids, dist_metric);
+    let (centroids, assignments) = enhance(points, centroids, assignments, dist_metric);
+
+    labels_to_clusters(points, &assignments, centroids.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perturb_moves_the_centroid_apart_in_opposite_directions() {
+        let centroid = vec![1.0, 2.0];
+        let up = perturb(&centroid, 1.0);
+        let down = perturb(&centroid, -1.0);
+        assert!(up[0] > centroid[0]);
+        assert!(down[0] < centroid[0]);
+    }
+
+    #[test]
+    fn lloyd_iterate_keeps_an_empty_clusters_centroid_in_place() {
+        let points = vec![vec![0.0], vec![0.0], vec![0.0]];
+        let centroids = vec![vec![0.0], vec![5.0]];
+        let (new_centroids, assignments) =
+            lloyd_iterate(&points, centroids, crate::euclidean_metric, 5);
+        assert!(assignments.iter().all(|&a| a == 0));
+        assert_eq!(new_centroids[1], vec![5.0]);
+    }
+
+    #[test]
+    fn lloyd_iterate_converges_centroids_onto_their_cluster_means() {
+        let points = vec![vec![0.0], vec![2.0], vec![10.0], vec![12.0]];
+        let centroids = vec![vec![0.0], vec![10.0]];
+        let (new_centroids, assignments) =
+            lloyd_iterate(&points, centroids, crate::euclidean_metric, MAX_LLOYD_ITERS);
+        assert_eq!(assignments, vec![0, 0, 1, 1]);
+        assert_eq!(new_centroids[0], vec![1.0]);
+        assert_eq!(new_centroids[1], vec![11.0]);
+    }
+
+    #[test]
+    fn cluster_returns_k_non_overlapping_clusters_covering_every_point() {
+        let points = vec![
+            vec![0.0, 0.0],
+            vec![0.1, 0.1],
+            vec![10.0, 10.0],
+            vec![10.1, 10.1],
+        ];
+        let clusters = cluster(&points, 2, crate::euclidean_metric);
+        assert_eq!(clusters.len(), 2);
+        let total: usize = clusters.values().map(|c| c.len()).sum();
+        assert_eq!(total, points.len());
+    }
+
+    #[test]
+    fn enhance_never_increases_total_distortion() {
+        let points = vec![
+            vec![0.0, 0.0],
+            vec![0.1, 0.1],
+            vec![10.0, 10.0],
+            vec![10.1, 10.1],
+        ];
+        let centroids = vec![vec![0.0, 0.0], vec![0.2, 0.2]];
+        let assignments = assign(&points, &centroids, crate::euclidean_metric);
+        let before = total_distortion(&points, &centroids, &assignments, crate::euclidean_metric);
+        let (new_centroids, new_assignments) =
+            enhance(&points, centroids, assignments, crate::euclidean_metric);
+        let after =
+            total_distortion(&points, &new_centroids, &new_assignments, crate::euclidean_metric);
+        assert!(after <= before);
+    }
+}
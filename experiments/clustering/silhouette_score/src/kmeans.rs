@@ -0,0 +1,236 @@
+/* k-means++ seeding and Lloyd's algorithm.
+
+k-means++ picks the first centroid uniformly at random, then repeatedly
+samples a point with probability proportional to its squared distance to
+the nearest already-chosen centroid (D^2 weighting) until k centroids
+exist. Lloyd's algorithm then alternates assigning each point to its
+nearest centroid and recomputing centroids as per-dimension means until
+assignments stop changing or a max-iteration cap is hit. Running several
+restarts and keeping the lowest within-cluster sum of squares (WCSS)
+guards against a single unlucky seeding.
+
+References:
+https://en.wikipedia.org/wiki/K-means%2B%2B */
+
+use crate::compute_mean;
+use rand::Rng;
+use std::collections::HashMap;
+
+type DistMetric = fn(&Vec<f64>, &Vec<f64>) -> f64;
+
+const MAX_LLOYD_ITERS: usize = 300;
+
+fn nearest_centroid(
+    point: &Vec<f64>,
+    centroids: &[Vec<f64>],
+    dist_metric: DistMetric,
+) -> (usize, f64) {
+    let mut best = 0;
+    let mut best_dist = f64::INFINITY;
+    for (i, centroid) in centroids.iter().enumerate() {
+        let d = dist_metric(point, centroid);
+        if d < best_dist {
+            best_dist = d;
+            best = i;
+        }
+    }
+    (best, best_dist)
+}
+
+fn assign(points: &[Vec<f64>], centroids: &[Vec<f64>], dist_metric: DistMetric) -> Vec<usize> {
+    points
+        .iter()
+        .map(|p| nearest_centroid(p, centroids, dist_metric).0)
+        .collect()
+}
+
+fn within_cluster_sum_of_squares(
+    points: &[Vec<f64>],
+    centroids: &[Vec<f64>],
+    assignments: &[usize],
+    dist_metric: DistMetric,
+) -> f64 {
+    points
+        .iter()
+        .zip(assignments)
+        .map(|(p, &a)| dist_metric(p, &centroids[a]).powi(2))
+        .sum()
+}
+
+fn recompute_centroids(
+    points: &[Vec<f64>],
+    assignments: &[usize],
+    k: usize,
+) -> Vec<Vec<f64>> {
+    let dims = points[0].len();
+    (0..k)
+        .map(|cluster| {
+            let members: Vec<&Vec<f64>> = points
+                .iter()
+                .zip(assignments)
+                .filter(|(_, &a)| a == cluster)
+                .map(|(p, _)| p)
+                .collect();
+            if members.is_empty() {
+                return points[cluster % points.len()].clone();
+            }
+            (0..dims)
+                .map(|d| {
+                    let column: Vec<f64> = members.iter().map(|p| p[d]).collect();
+                    compute_mean(&column)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// k-means++ initialization: the first centroid is uniform over the points,
+// each subsequent one is sampled with probability proportional to its
+// squared distance to the nearest already-chosen centroid.
+fn init_plus_plus(
+    points: &[Vec<f64>],
+    k: usize,
+    dist_metric: DistMetric,
+    rng: &mut impl Rng,
+) -> Vec<Vec<f64>> {
+    let mut centroids = vec![points[rng.gen_range(0..points.len())].clone()];
+
+    while centroids.len() < k {
+        let weights: Vec<f64> = points
+            .iter()
+            .map(|p| nearest_centroid(p, &centroids, dist_metric).1.powi(2))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        if total == 0.0 {
+            centroids.push(points[rng.gen_range(0..points.len())].clone());
+            continue;
+        }
+        let mut target = rng.gen::<f64>() * total;
+        let mut chosen = points.len() - 1;
+        for (i, &w) in weights.iter().enumerate() {
+            if target < w {
+                chosen = i;
+                break;
+            }
+            target -= w;
+        }
+        centroids.push(points[chosen].clone());
+    }
+
+    centroids
+}
+
+fn lloyd_iterate(
+    points: &[Vec<f64>],
+    mut centroids: Vec<Vec<f64>>,
+    k: usize,
+    dist_metric: DistMetric,
+) -> (Vec<Vec<f64>>, Vec<usize>) {
+    let mut assignments = assign(points, &centroids, dist_metric);
+    for _ in 0..MAX_LLOYD_ITERS {
+        centroids = recompute_centroids(points, &assignments, k);
+        let new_assignments = assign(points, &centroids, dist_metric);
+        if new_assignments == assignments {
+            break;
+        }
+        assignments = new_assignments;
+    }
+    (centroids, assignments)
+}
+
+fn labels_to_clusters(points: &[Vec<f64>], assignments: &[usize]) -> HashMap<i64, Vec<Vec<f64>>> {
+    let mut clusters: HashMap<i64, Vec<Vec<f64>>> = HashMap::new();
+    for (point, &label) in points.iter().zip(assignments) {
+        clusters
+            .entry(label as i64)
+            .or_insert_with(Vec::new)
+            .push(point.clone());
+    }
+    clusters
+}
+
+// Clusters `points` into `k` groups with k-means++ seeded Lloyd's algorithm,
+// keeping the assignment with lowest WCSS across `restarts` independent
+// runs. Returns the labeled clusters for `compute_silhouette_score` plus the
+// raw per-point labels so the caller can write them back to Parquet.
+pub fn cluster(
+    points: &Vec<Vec<f64>>,
+    k: usize,
+    dist_metric: DistMetric,
+    restarts: usize,
+) -> (HashMap<i64, Vec<Vec<f64>>>, Vec<i64>) {
+    assert!(!points.is_empty(), "Cannot cluster an empty dataset.");
+    assert!(k >= 1 && k <= points.len(), "k must be between 1 and the number of points.");
+    assert!(restarts >= 1, "restarts must be at least 1.");
+
+    let mut rng = rand::thread_rng();
+    let mut best_assignments: Option<Vec<usize>> = None;
+    let mut best_wcss = f64::INFINITY;
+
+    for _ in 0..restarts {
+        let centroids = init_plus_plus(points, k, dist_metric, &mut rng);
+        let (centroids, assignments) = lloyd_iterate(points, centroids, k, dist_metric);
+        let wcss = within_cluster_sum_of_squares(points, &centroids, &assignments, dist_metric);
+        if wcss < best_wcss {
+            best_wcss = wcss;
+            best_assignments = Some(assignments);
+        }
+    }
+
+    let assignments = best_assignments.unwrap();
+    let labels: Vec<i64> = assignments.iter().map(|&a| a as i64).collect();
+    (labels_to_clusters(points, &assignments), labels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lloyd_iterate_converges_centroids_onto_their_cluster_means() {
+        let points = vec![vec![0.0], vec![2.0], vec![10.0], vec![12.0]];
+        let centroids = vec![vec![0.0], vec![10.0]];
+        let (new_centroids, assignments) =
+            lloyd_iterate(&points, centroids, 2, crate::euclidean_metric);
+        assert_eq!(assignments, vec![0, 0, 1, 1]);
+        assert_eq!(new_centroids[0], vec![1.0]);
+        assert_eq!(new_centroids[1], vec![11.0]);
+    }
+
+    #[test]
+    fn recompute_centroids_keeps_an_empty_clusters_slot_filled() {
+        let points = vec![vec![0.0], vec![1.0]];
+        // both points assigned to cluster 0; cluster 1 has no members.
+        let assignments = vec![0, 0];
+        let centroids = recompute_centroids(&points, &assignments, 2);
+        assert_eq!(centroids[0], vec![0.5]);
+        assert!(points.iter().any(|p| *p == centroids[1]));
+    }
+
+    #[test]
+    fn cluster_separates_two_well_separated_blobs() {
+        let points = vec![
+            vec![0.0, 0.0],
+            vec![0.1, -0.1],
+            vec![-0.1, 0.1],
+            vec![20.0, 20.0],
+            vec![20.1, 19.9],
+            vec![19.9, 20.1],
+        ];
+        let (clusters, labels) = cluster(&points, 2, crate::euclidean_metric, 5);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(labels.len(), points.len());
+        assert_eq!(labels[0], labels[1]);
+        assert_eq!(labels[1], labels[2]);
+        assert_eq!(labels[3], labels[4]);
+        assert_eq!(labels[4], labels[5]);
+        assert_ne!(labels[0], labels[3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn cluster_rejects_k_greater_than_point_count() {
+        let points = vec![vec![0.0], vec![1.0]];
+        cluster(&points, 3, crate::euclidean_metric, 1);
+    }
+}
@@ -1,31 +1,110 @@
 /* Calculate the Silhouette score for a dataset of clusters specified by the input path.
 
+By default this uses a fast centroid-based approximation of a(i) and b(i),
+which is O(n * k) with `--representative centroid` but biased on non-spherical or uneven clusters. Pass
+`--exact` to compute the real pairwise-distance definition instead, which is
+O(n^2) but correct.
+
+The input Parquet file is expected to already carry a "cluster" label column.
+To cluster unlabeled data first, pass `--cluster-method <method>`:
+  - `elbg` (see `elbg.rs`): LBG/ELBG vector quantization, requires `--k`
+  - `kmeans` (see `kmeans.rs`): k-means++ seeded Lloyd's algorithm, requires
+    `--k`; also supports `--restarts <N>` to keep the lowest-WCSS run, and
+    `--output <path>` to write the labels back out as a Parquet file
+  - `threshold` (see `threshold.rs`): union-find connected components,
+    requires `--threshold <tau>` instead of a fixed K
+  - `median-cut` (see `median_cut.rs`): recursive box partitioning, requires
+    `--max-boxes <N>` and optionally `--min-spread <S>` (default 0) to stop
+    splitting early; sweep `--max-boxes` and compare silhouette scores to
+    locate a good cluster count without choosing K up front
+The labels any of these produce are scored directly.
+
+`--metric <euclidean|manhattan|chebyshev|cosine>` (default euclidean) selects
+the distance function used by `elbg`, `kmeans` and `threshold` and in
+scoring; `median-cut` splits on raw coordinates and does not use it. `--representative
+<centroid|medoid>` (default centroid) selects how the fast silhouette path
+summarizes a cluster: the synthetic per-dimension median, or the medoid (the
+actual member minimizing total distance to the rest of its cluster), which is
+usually the better choice for non-Euclidean metrics. The two flags are
+independent of each other and of `--cluster-method`.
+
 Example run:
-cargo run -- ~/data/algohertz/clustering/datasets/clusters_100_5.parquet
+cargo run -- ~/data/algohertz/clustering/datasets/clusters_100_5.parquet --exact
+cargo run -- ~/data/algohertz/clustering/datasets/unlabeled_100_5.parquet --cluster-method elbg --k 5
+cargo run -- ~/data/algohertz/clustering/datasets/unlabeled_100_5.parquet --cluster-method kmeans --k 5 --restarts 10 --output /tmp/labeled.parquet
+cargo run -- ~/data/algohertz/clustering/datasets/unlabeled_100_5.parquet --cluster-method threshold --threshold 0.5
+cargo run -- ~/data/algohertz/clustering/datasets/clusters_100_5.parquet --metric cosine --representative medoid
+cargo run -- ~/data/algohertz/clustering/datasets/unlabeled_100_5.parquet --cluster-method median-cut --max-boxes 8
 
 References:
 https://en.wikipedia.org/wiki/Silhouette_(clustering)
+https://en.wikipedia.org/wiki/Linde%E2%80%93Buzo%E2%80%93Gray_algorithm
+https://en.wikipedia.org/wiki/K-means%2B%2B
 
 Copyright © 2024 AlgoHertz. License: MIT. */
 
+mod elbg;
+mod kmeans;
+mod median_cut;
+mod threshold;
+mod union_find;
+
 use polars::prelude::*;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Result;
 
+type DistMetric = fn(&Vec<f64>, &Vec<f64>) -> f64;
+
 fn compute_centroid(points: &Vec<Vec<f64>>) -> Vec<f64> {
     if points.len() == 0 {
         panic!("Cannot compute a centroid of an empty cluster.");
     }
+    let dims = points.get(0).unwrap().len();
     let mut centroid = vec![];
-    for i in 0..points.get(0).unwrap().len() {
-        let median = compute_median(&points[i]);
-        centroid.push(median);        
+    for d in 0..dims {
+        let column: Vec<f64> = points.iter().map(|p| p[d]).collect();
+        let median = compute_median(&column);
+        centroid.push(median);
     }
     return centroid;
 }
 
-fn compute_median(values: &Vec<f64>) -> f64 {
+// Ignores `dist_metric` so it can stand in anywhere `compute_medoid` can:
+// the centroid is a synthetic per-dimension median, not an actual member.
+fn compute_centroid_representative(points: &Vec<Vec<f64>>, _dist_metric: DistMetric) -> Vec<f64> {
+    compute_centroid(points)
+}
+
+// The actual cluster member minimizing the sum of distances (under
+// `dist_metric`) to every other member. More meaningful than a synthetic
+// centroid for metrics like L1 or cosine where "the mean point" doesn't
+// correspond to anything in the data.
+fn compute_medoid(points: &Vec<Vec<f64>>, dist_metric: DistMetric) -> Vec<f64> {
+    if points.len() == 0 {
+        panic!("Cannot compute a medoid of an empty cluster.");
+    }
+    let mut best_index = 0;
+    let mut best_sum = f64::INFINITY;
+    for (i, candidate) in points.iter().enumerate() {
+        let sum: f64 = points.iter().map(|other| dist_metric(candidate, other)).sum();
+        if sum < best_sum {
+            best_sum = sum;
+            best_index = i;
+        }
+    }
+    points[best_index].clone()
+}
+
+fn resolve_representative(name: &str) -> fn(&Vec<Vec<f64>>, DistMetric) -> Vec<f64> {
+    match name {
+        "centroid" => compute_centroid_representative,
+        "medoid" => compute_medoid,
+        other => panic!("Unknown --representative: {other}"),
+    }
+}
+
+pub(crate) fn compute_median(values: &Vec<f64>) -> f64 {
     let mut sorted_values = values.clone();
     sorted_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
 
@@ -43,7 +122,7 @@ fn compute_median(values: &Vec<f64>) -> f64 {
     }
 }
 
-fn compute_mean(values: &Vec<f64>) -> f64 {
+pub(crate) fn compute_mean(values: &Vec<f64>) -> f64 {
     let mut sum: f64 = 0.0;
     for v in values {
         sum += v;
@@ -66,24 +145,89 @@ fn euclidean_metric(point_a: &Vec<f64>, point_b: &Vec<f64>) -> f64 {
     sum_of_squares.sqrt()
 }
 
-fn compute_silhouette_score(
+fn manhattan_metric(point_a: &Vec<f64>, point_b: &Vec<f64>) -> f64 {
+    if point_a.len() != point_b.len() {
+        panic!("Points must have the same dimension");
+    }
+
+    point_a
+        .iter()
+        .zip(point_b.iter())
+        .map(|(a, b)| (a - b).abs())
+        .sum()
+}
+
+fn chebyshev_metric(point_a: &Vec<f64>, point_b: &Vec<f64>) -> f64 {
+    if point_a.len() != point_b.len() {
+        panic!("Points must have the same dimension");
+    }
+
+    point_a
+        .iter()
+        .zip(point_b.iter())
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max)
+}
+
+// 1 - cosine similarity, so that identical directions score 0 and opposite
+// directions score 2, consistent with the other metrics being 0 at identity.
+fn cosine_metric(point_a: &Vec<f64>, point_b: &Vec<f64>) -> f64 {
+    if point_a.len() != point_b.len() {
+        panic!("Points must have the same dimension");
+    }
+
+    let dot: f64 = point_a.iter().zip(point_b.iter()).map(|(a, b)| a * b).sum();
+    let norm_a: f64 = point_a.iter().map(|a| a * a).sum::<f64>().sqrt();
+    let norm_b: f64 = point_b.iter().map(|b| b * b).sum::<f64>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 1.0;
+    }
+
+    1.0 - dot / (norm_a * norm_b)
+}
+
+fn resolve_metric(name: &str) -> DistMetric {
+    match name {
+        "euclidean" => euclidean_metric,
+        "manhattan" => manhattan_metric,
+        "chebyshev" => chebyshev_metric,
+        "cosine" => cosine_metric,
+        other => panic!("Unknown --metric: {other}"),
+    }
+}
+
+// Fast approximation: a(i) is distance from i to its own cluster's centroid
+// (scaled by n/(n-1)), b(i) is distance to the nearest other cluster's
+// centroid. O(n * k) instead of O(n^2) with the centroid representative (the
+// medoid representative adds a pairwise pass within each cluster), but
+// biased on non-spherical or uneven clusters since it collapses each cluster
+// down to one point.
+fn compute_silhouette_score_fast(
     clusters: &HashMap<i64, Vec<Vec<f64>>>,
-    dist_metric: fn(&Vec<f64>, &Vec<f64>) -> f64,
+    dist_metric: DistMetric,
+    representative: fn(&Vec<Vec<f64>>, DistMetric) -> Vec<f64>,
 ) -> f64 {
     let mut scores: Vec<f64> = vec![];
     let cluster_numbers = clusters.keys();
     let mut centroids: HashMap<i64, Vec<f64>> = HashMap::with_capacity(cluster_numbers.len());
     for cluster_number in cluster_numbers.clone() {
-        centroids.insert(*cluster_number, compute_centroid(clusters.get(&cluster_number).unwrap()));
+        centroids.insert(
+            *cluster_number,
+            representative(clusters.get(&cluster_number).unwrap(), dist_metric),
+        );
     }
-    
+
     for cluster_number in cluster_numbers.clone() {
         let points = clusters.get(&cluster_number).unwrap();
+        let is_singleton = points.len() == 1;
         let ratio = points.len() as f64 / (points.len() as f64 - 1.0);
         let cluster_centroid = centroids.get(cluster_number).unwrap();
         for point in points {
-            let d = dist_metric(&point, &cluster_centroid);
-            let a = ratio * d;
+            let a = if is_singleton {
+                0.0
+            } else {
+                ratio * dist_metric(&point, &cluster_centroid)
+            };
 
             let mut b = f64::INFINITY;
             for cluster_number2 in cluster_numbers.clone() {
@@ -96,7 +240,7 @@ fn compute_silhouette_score(
                     b = d;
                 }
             }
-            let s = (b - a) / a.max(b);
+            let s = if is_singleton { 0.0 } else { (b - a) / a.max(b) };
             scores.push(s);
         }
     }
@@ -104,14 +248,105 @@ fn compute_silhouette_score(
     return score;
 }
 
-fn main() -> Result<()> {
-    let path = std::env::args()
-        .nth(1)
-        .expect("Please provide a Parquet file path to the cluster dataset.");
+// Exact silhouette per https://en.wikipedia.org/wiki/Silhouette_(clustering):
+// a(i) is the mean distance from i to every other point in its own cluster,
+// b(i) is the minimum, over all other clusters, of the mean distance from i
+// to every point in that cluster. O(n^2) in the number of points, so it is
+// only practical for small-to-medium datasets; `compute_silhouette_score_fast`
+// exists for everything else.
+fn compute_silhouette_score_exact(
+    clusters: &HashMap<i64, Vec<Vec<f64>>>,
+    dist_metric: DistMetric,
+) -> f64 {
+    let mut scores: Vec<f64> = vec![];
+    let cluster_numbers: Vec<&i64> = clusters.keys().collect();
 
-    let df = ParquetReader::new(File::open(path)?)
-        .finish()
-        .expect("Failed to read Parquet file");
+    for &cluster_number in &cluster_numbers {
+        let points = clusters.get(cluster_number).unwrap();
+        for point in points {
+            let a = if points.len() == 1 {
+                0.0
+            } else {
+                let sum: f64 = points
+                    .iter()
+                    .map(|other| dist_metric(point, other))
+                    .sum();
+                sum / (points.len() as f64 - 1.0)
+            };
+
+            let mut b = f64::INFINITY;
+            for &cluster_number2 in &cluster_numbers {
+                if cluster_number == cluster_number2 {
+                    continue;
+                }
+                let other_points = clusters.get(cluster_number2).unwrap();
+                let mean_dist = compute_mean(
+                    &other_points
+                        .iter()
+                        .map(|other| dist_metric(point, other))
+                        .collect(),
+                );
+                if mean_dist < b {
+                    b = mean_dist;
+                }
+            }
+
+            let s = if points.len() == 1 {
+                0.0
+            } else {
+                (b - a) / a.max(b)
+            };
+            scores.push(s);
+        }
+    }
+    let score = compute_mean(&scores);
+    return score;
+}
+
+fn compute_silhouette_score(
+    clusters: &HashMap<i64, Vec<Vec<f64>>>,
+    dist_metric: DistMetric,
+    representative: fn(&Vec<Vec<f64>>, DistMetric) -> Vec<f64>,
+    exact: bool,
+) -> f64 {
+    if exact {
+        compute_silhouette_score_exact(clusters, dist_metric)
+    } else {
+        compute_silhouette_score_fast(clusters, dist_metric, representative)
+    }
+}
+
+// Reads every column of `df` as a feature of an unlabeled point, for
+// clustering methods that produce their own labels rather than consuming
+// pre-labeled data.
+fn read_points(df: &DataFrame) -> Vec<Vec<f64>> {
+    let mut points = Vec::with_capacity(df.height());
+    for idx in 0..df.height() {
+        let row = df.get_row(idx);
+        let result = row.unwrap().0;
+        let mut point: Vec<f64> = vec![];
+        for value in result {
+            let v = match value {
+                AnyValue::Float64(val) => val,
+                AnyValue::Int64(val) => val as f64,
+                AnyValue::UInt32(val) => val as f64,
+                other => panic!("Unsupported column type in unlabeled dataset: {:?}", other),
+            };
+            point.push(v);
+        }
+        points.push(point);
+    }
+    points
+}
+
+fn get_flag_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|idx| args.get(idx + 1))
+        .cloned()
+}
+
+fn read_labeled_clusters(df: &DataFrame) -> HashMap<i64, Vec<Vec<f64>>> {
     assert!(df.get_column_names()[0] == "cluster");
 
     let binding = df.column("cluster").unwrap().unique().unwrap();
@@ -147,9 +382,195 @@ fn main() -> Result<()> {
         }
         clusters.get_mut(&cluster).unwrap().push(point);
     }
+    clusters
+}
+
+// Writes `points` and their `labels` out as a Parquet file with a "cluster"
+// column followed by one "feature_N" column per dimension, so the result is
+// round-trippable through `ParquetReader` / `read_labeled_clusters`.
+fn write_labeled_parquet(points: &Vec<Vec<f64>>, labels: &Vec<i64>, path: &str) {
+    let dims = points.get(0).map(|p| p.len()).unwrap_or(0);
+    let mut columns: Vec<Column> = Vec::with_capacity(dims + 1);
+    columns.push(Series::new("cluster".into(), labels.clone()).into());
+    for d in 0..dims {
+        let column: Vec<f64> = points.iter().map(|p| p[d]).collect();
+        columns.push(Series::new(format!("feature_{d}").into(), column).into());
+    }
+
+    let mut labeled_df = DataFrame::new(columns).expect("Failed to build output DataFrame");
+    let file = File::create(path).expect("Failed to create output Parquet file");
+    ParquetWriter::new(file)
+        .finish(&mut labeled_df)
+        .expect("Failed to write output Parquet file");
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let path = args
+        .get(1)
+        .expect("Please provide a Parquet file path to the cluster dataset.")
+        .clone();
+    let exact = args.iter().any(|arg| arg == "--exact");
+    let cluster_method = get_flag_value(&args, "--cluster-method");
+    let k = get_flag_value(&args, "--k").map(|v| {
+        v.parse::<usize>()
+            .expect("--k must be a positive integer")
+    });
+    let restarts = get_flag_value(&args, "--restarts")
+        .map(|v| {
+            v.parse::<usize>()
+                .expect("--restarts must be a positive integer")
+        })
+        .unwrap_or(1);
+    let output_path = get_flag_value(&args, "--output");
+    let metric = resolve_metric(&get_flag_value(&args, "--metric").unwrap_or("euclidean".to_string()));
+    let representative =
+        resolve_representative(&get_flag_value(&args, "--representative").unwrap_or("centroid".to_string()));
+
+    let df = ParquetReader::new(File::open(path)?)
+        .finish()
+        .expect("Failed to read Parquet file");
+
+    let clusters: HashMap<i64, Vec<Vec<f64>>> = match cluster_method.as_deref() {
+        Some("elbg") => {
+            let k = k.expect("--cluster-method elbg requires --k");
+            let points = read_points(&df);
+            elbg::cluster(&points, k, metric)
+        }
+        Some("kmeans") => {
+            let k = k.expect("--cluster-method kmeans requires --k");
+            let points = read_points(&df);
+            let (clusters, labels) = kmeans::cluster(&points, k, metric, restarts);
+            if let Some(output_path) = &output_path {
+                write_labeled_parquet(&points, &labels, output_path);
+            }
+            clusters
+        }
+        Some("threshold") => {
+            let tau = get_flag_value(&args, "--threshold")
+                .expect("--cluster-method threshold requires --threshold")
+                .parse::<f64>()
+                .expect("--threshold must be a number");
+            let points = read_points(&df);
+            threshold::cluster(&points, tau, metric)
+        }
+        Some("median-cut") => {
+            let max_boxes = get_flag_value(&args, "--max-boxes")
+                .expect("--cluster-method median-cut requires --max-boxes")
+                .parse::<usize>()
+                .expect("--max-boxes must be a positive integer");
+            let min_spread = get_flag_value(&args, "--min-spread")
+                .map(|v| v.parse::<f64>().expect("--min-spread must be a number"))
+                .unwrap_or(0.0);
+            let points = read_points(&df);
+            median_cut::cluster(&points, max_boxes, min_spread)
+        }
+        Some(other) => panic!("Unknown --cluster-method: {other}"),
+        None => read_labeled_clusters(&df),
+    };
 
-    let silhouette_score = compute_silhouette_score(&clusters, euclidean_metric);
+    let silhouette_score = compute_silhouette_score(&clusters, metric, representative, exact);
     println!("{:?}", silhouette_score);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn exact_silhouette_matches_a_hand_computed_multi_cluster_score() {
+        let mut clusters: HashMap<i64, Vec<Vec<f64>>> = HashMap::new();
+        clusters.insert(0, vec![vec![1.0], vec![2.0], vec![3.0]]);
+        clusters.insert(1, vec![vec![10.0], vec![11.0]]);
+
+        let score = compute_silhouette_score_exact(&clusters, euclidean_metric);
+
+        // s(1.0) = (9.5 - 1.5) / 9.5, s(2.0) = (8.5 - 1) / 8.5, s(3.0) = (7.5 - 1.5) / 7.5,
+        // s(10.0) = (8 - 1) / 8, s(11.0) = (9 - 1) / 9, averaged over all 5 points.
+        let expected = (8.0 / 9.5 + 7.5 / 8.5 + 6.0 / 7.5 + 7.0 / 8.0 + 8.0 / 9.0) / 5.0;
+        assert_close(score, expected);
+    }
+
+    #[test]
+    fn exact_silhouette_scores_a_singleton_cluster_member_as_zero() {
+        let mut clusters: HashMap<i64, Vec<Vec<f64>>> = HashMap::new();
+        clusters.insert(0, vec![vec![0.0]]);
+        clusters.insert(1, vec![vec![10.0], vec![20.0]]);
+
+        let score = compute_silhouette_score_exact(&clusters, euclidean_metric);
+
+        // The singleton's s(i) is forced to 0. The two-member cluster scores
+        // s(10.0) = (10 - 10) / 10 = 0 and s(20.0) = (20 - 10) / 20 = 0.5.
+        let expected = (0.0 + 0.0 + 0.5) / 3.0;
+        assert_close(score, expected);
+    }
+
+    #[test]
+    fn manhattan_metric_sums_absolute_coordinate_differences() {
+        assert_close(manhattan_metric(&vec![1.0, 2.0], &vec![1.0, 2.0]), 0.0);
+        assert_close(manhattan_metric(&vec![0.0, 0.0], &vec![3.0, -4.0]), 7.0);
+    }
+
+    #[test]
+    fn chebyshev_metric_takes_the_largest_coordinate_difference() {
+        assert_close(chebyshev_metric(&vec![1.0, 2.0], &vec![1.0, 2.0]), 0.0);
+        assert_close(chebyshev_metric(&vec![0.0, 0.0], &vec![3.0, -4.0]), 4.0);
+    }
+
+    #[test]
+    fn cosine_metric_is_zero_for_identical_and_two_for_opposite_vectors() {
+        assert_close(cosine_metric(&vec![1.0, 2.0], &vec![1.0, 2.0]), 0.0);
+        assert_close(cosine_metric(&vec![1.0, 0.0], &vec![-1.0, 0.0]), 2.0);
+        assert_close(cosine_metric(&vec![1.0, 0.0], &vec![0.0, 1.0]), 1.0);
+    }
+
+    #[test]
+    fn compute_medoid_picks_the_member_with_least_total_distance() {
+        let points = vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![5.0, 5.0]];
+        let medoid = compute_medoid(&points, euclidean_metric);
+        assert_eq!(medoid, vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn compute_centroid_takes_the_median_of_each_dimension_independently() {
+        // Regression test: compute_centroid once indexed by point instead of
+        // by dimension column, which silently returned the wrong vector here.
+        let points = vec![
+            vec![1.0, 100.0],
+            vec![2.0, 200.0],
+            vec![3.0, 300.0],
+            vec![4.0, 400.0],
+            vec![5.0, 500.0],
+        ];
+        assert_eq!(compute_centroid(&points), vec![3.0, 300.0]);
+        assert_eq!(
+            compute_centroid_representative(&points, euclidean_metric),
+            vec![3.0, 300.0]
+        );
+    }
+
+    #[test]
+    fn resolve_metric_and_resolve_representative_dispatch_by_name() {
+        let metric = resolve_metric("cosine");
+        assert_close(
+            metric(&vec![1.0, 0.0], &vec![0.0, 1.0]),
+            cosine_metric(&vec![1.0, 0.0], &vec![0.0, 1.0]),
+        );
+
+        let points = vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![5.0, 5.0]];
+        let representative = resolve_representative("medoid");
+        assert_eq!(
+            representative(&points, euclidean_metric),
+            compute_medoid(&points, euclidean_metric)
+        );
+    }
+}
@@ -0,0 +1,147 @@
+/* Median-cut box partitioning.
+
+Starts with a single box containing every point. Repeatedly picks the box
+with the greatest spread along any dimension, finds that dimension's median
+coordinate (via `compute_median`), and splits the box into two halves at the
+median. Splitting stops once a target number of boxes is reached, or no
+remaining box's widest dimension exceeds a minimum-spread threshold. Each
+final box becomes a cluster whose representative is the per-dimension
+median, matching what `compute_centroid` computes once it builds each
+dimension's column across the cluster (rather than indexing by point), so no
+new representative mode is needed here. This needs no target K up front,
+making it useful for sweeping box counts to locate a good cluster count via
+the resulting silhouette scores. */
+
+use crate::compute_median;
+use std::collections::HashMap;
+
+// Greatest spread (max - min) of `indices` along each dimension, and which
+// dimension achieves it.
+fn widest_dimension(points: &[Vec<f64>], indices: &[usize]) -> (usize, f64) {
+    let dims = points[0].len();
+    let mut best_dim = 0;
+    let mut best_spread = f64::NEG_INFINITY;
+    for dim in 0..dims {
+        let values: Vec<f64> = indices.iter().map(|&i| points[i][dim]).collect();
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let spread = max - min;
+        if spread > best_spread {
+            best_spread = spread;
+            best_dim = dim;
+        }
+    }
+    (best_dim, best_spread)
+}
+
+fn split_box(points: &[Vec<f64>], indices: &[usize], dim: usize) -> (Vec<usize>, Vec<usize>) {
+    let values: Vec<f64> = indices.iter().map(|&i| points[i][dim]).collect();
+    let median = compute_median(&values);
+
+    let (lower, upper): (Vec<usize>, Vec<usize>) =
+        indices.iter().partition(|&&i| points[i][dim] <= median);
+    if !lower.is_empty() && !upper.is_empty() {
+        return (lower, upper);
+    }
+
+    // The median equals the largest coordinate along this dimension (more
+    // than half the members share that value), so every member landed on the
+    // lower side of the value-based split. Fall back to an even split by rank
+    // so the box still divides.
+    let mut ranked = indices.to_vec();
+    ranked.sort_by(|&a, &b| points[a][dim].partial_cmp(&points[b][dim]).unwrap());
+    let mid = ranked.len() / 2;
+    (ranked[..mid].to_vec(), ranked[mid..].to_vec())
+}
+
+fn labels_to_clusters(points: &[Vec<f64>], boxes: &[Vec<usize>]) -> HashMap<i64, Vec<Vec<f64>>> {
+    let mut clusters: HashMap<i64, Vec<Vec<f64>>> = HashMap::with_capacity(boxes.len());
+    for (label, indices) in boxes.iter().enumerate() {
+        let members = indices.iter().map(|&i| points[i].clone()).collect();
+        clusters.insert(label as i64, members);
+    }
+    clusters
+}
+
+// Partitions `points` into at most `max_boxes` clusters via median-cut,
+// stopping early once no box's widest dimension exceeds `min_spread`.
+pub fn cluster(
+    points: &Vec<Vec<f64>>,
+    max_boxes: usize,
+    min_spread: f64,
+) -> HashMap<i64, Vec<Vec<f64>>> {
+    assert!(!points.is_empty(), "Cannot cluster an empty dataset.");
+    assert!(max_boxes >= 1, "max_boxes must be at least 1.");
+
+    let mut boxes: Vec<Vec<usize>> = vec![(0..points.len()).collect()];
+
+    while boxes.len() < max_boxes {
+        let mut candidate: Option<(usize, usize, f64)> = None; // (box index, dim, spread)
+        for (i, indices) in boxes.iter().enumerate() {
+            if indices.len() < 2 {
+                continue;
+            }
+            let (dim, spread) = widest_dimension(points, indices);
+            if candidate.map_or(true, |(_, _, best)| spread > best) {
+                candidate = Some((i, dim, spread));
+            }
+        }
+
+        let Some((box_index, dim, spread)) = candidate else {
+            break;
+        };
+        if spread <= min_spread {
+            break;
+        }
+
+        let indices = boxes.remove(box_index);
+        let (lower, upper) = split_box(points, &indices, dim);
+        boxes.push(lower);
+        boxes.push(upper);
+    }
+
+    labels_to_clusters(points, &boxes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_until_max_boxes_is_reached() {
+        let points = vec![vec![0.0], vec![1.0], vec![2.0], vec![3.0]];
+        let clusters = cluster(&points, 2, 0.0);
+        assert_eq!(clusters.len(), 2);
+        let total: usize = clusters.values().map(|c| c.len()).sum();
+        assert_eq!(total, points.len());
+    }
+
+    #[test]
+    fn stops_early_once_no_box_exceeds_min_spread() {
+        let points = vec![vec![0.0], vec![0.0], vec![0.0], vec![0.0]];
+        let clusters = cluster(&points, 4, 0.0);
+        // every point is identical, so spread is always zero and no split occurs.
+        assert_eq!(clusters.len(), 1);
+    }
+
+    #[test]
+    fn falls_back_to_a_rank_split_when_the_value_split_collapses() {
+        // the median (5.0) equals the largest value, so the naive <= split
+        // would put every point on the lower side.
+        let points = vec![vec![1.0], vec![5.0], vec![5.0], vec![5.0], vec![5.0]];
+        let indices: Vec<usize> = (0..points.len()).collect();
+        let (lower, upper) = split_box(&points, &indices, 0);
+        assert!(!lower.is_empty());
+        assert!(!upper.is_empty());
+        assert_eq!(lower.len() + upper.len(), points.len());
+    }
+
+    #[test]
+    fn widest_dimension_picks_the_dimension_with_the_greatest_range() {
+        let points = vec![vec![0.0, 0.0], vec![1.0, 100.0]];
+        let indices: Vec<usize> = (0..points.len()).collect();
+        let (dim, spread) = widest_dimension(&points, &indices);
+        assert_eq!(dim, 1);
+        assert_eq!(spread, 100.0);
+    }
+}
@@ -0,0 +1,73 @@
+/* Threshold-based transitive-closure clustering.
+
+No target cluster count is needed: two points are linked whenever
+`dist_metric(a, b) <= tau`, and clusters are the connected components of
+that graph. A point with no neighbor within tau ends up as its own
+singleton cluster. This lets a user sweep tau, or skip choosing K
+altogether, and still get a clustering to score with
+`compute_silhouette_score`. */
+
+use crate::union_find::DisjointSet;
+use std::collections::HashMap;
+
+type DistMetric = fn(&Vec<f64>, &Vec<f64>) -> f64;
+
+pub fn cluster(
+    points: &Vec<Vec<f64>>,
+    tau: f64,
+    dist_metric: DistMetric,
+) -> HashMap<i64, Vec<Vec<f64>>> {
+    assert!(!points.is_empty(), "Cannot cluster an empty dataset.");
+
+    let mut sets = DisjointSet::new(points.len());
+    for i in 0..points.len() {
+        for j in (i + 1)..points.len() {
+            if dist_metric(&points[i], &points[j]) <= tau {
+                sets.union(i, j);
+            }
+        }
+    }
+
+    let mut root_to_label: HashMap<usize, i64> = HashMap::new();
+    let mut clusters: HashMap<i64, Vec<Vec<f64>>> = HashMap::new();
+    for (i, point) in points.iter().enumerate() {
+        let root = sets.find(i);
+        let next_label = root_to_label.len() as i64;
+        let label = *root_to_label.entry(root).or_insert(next_label);
+        clusters.entry(label).or_insert_with(Vec::new).push(point.clone());
+    }
+
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_points_within_tau_and_separates_the_rest() {
+        let points = vec![vec![0.0, 0.0], vec![0.1, 0.0], vec![10.0, 10.0]];
+        let clusters = cluster(&points, 0.5, crate::euclidean_metric);
+        assert_eq!(clusters.len(), 2);
+        let mut sizes: Vec<usize> = clusters.values().map(|c| c.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 2]);
+    }
+
+    #[test]
+    fn every_point_is_its_own_singleton_when_tau_is_zero_and_points_differ() {
+        let points = vec![vec![0.0], vec![1.0], vec![2.0]];
+        let clusters = cluster(&points, 0.0, crate::euclidean_metric);
+        assert_eq!(clusters.len(), 3);
+    }
+
+    #[test]
+    fn links_are_transitive_through_a_shared_neighbor() {
+        // a-b and b-c are within tau, but a-c alone would not be; clustering
+        // still merges all three via the connected-components closure.
+        let points = vec![vec![0.0], vec![1.0], vec![2.0]];
+        let clusters = cluster(&points, 1.0, crate::euclidean_metric);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters.values().next().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,92 @@
+/* Disjoint-set (union-find) with path compression and union-by-rank. */
+
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    rank: Vec<usize>,
+}
+
+impl DisjointSet {
+    pub fn new(size: usize) -> Self {
+        DisjointSet {
+            parent: (0..size).collect(),
+            rank: vec![0; size],
+        }
+    }
+
+    pub fn find(&mut self, x: usize) -> usize {
+        if self.parent[x] != x {
+            self.parent[x] = self.find(self.parent[x]);
+        }
+        self.parent[x]
+    }
+
+    pub fn union(&mut self, a: usize, b: usize) {
+        let root_a = self.find(a);
+        let root_b = self.find(b);
+        if root_a == root_b {
+            return;
+        }
+        if self.rank[root_a] < self.rank[root_b] {
+            self.parent[root_a] = root_b;
+        } else if self.rank[root_a] > self.rank[root_b] {
+            self.parent[root_b] = root_a;
+        } else {
+            self.parent[root_b] = root_a;
+            self.rank[root_a] += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_initializes_each_element_as_its_own_root() {
+        let mut sets = DisjointSet::new(4);
+        for i in 0..4 {
+            assert_eq!(sets.find(i), i);
+        }
+    }
+
+    #[test]
+    fn union_merges_two_sets_under_a_single_root() {
+        let mut sets = DisjointSet::new(4);
+        sets.union(0, 1);
+        assert_eq!(sets.find(0), sets.find(1));
+        assert_ne!(sets.find(0), sets.find(2));
+    }
+
+    #[test]
+    fn union_is_transitive_across_chained_unions() {
+        let mut sets = DisjointSet::new(5);
+        sets.union(0, 1);
+        sets.union(1, 2);
+        sets.union(3, 4);
+        assert_eq!(sets.find(0), sets.find(2));
+        assert_ne!(sets.find(0), sets.find(3));
+    }
+
+    #[test]
+    fn union_by_rank_keeps_the_first_arguments_root_on_tie() {
+        let mut sets = DisjointSet::new(4);
+        sets.union(0, 1);
+        sets.union(2, 3);
+        // both sets have rank 1; the tie-break makes root_a (0's root) win.
+        sets.union(0, 2);
+        assert_eq!(sets.find(2), sets.find(0));
+        assert_eq!(sets.find(0), 0);
+    }
+
+    #[test]
+    fn path_compression_keeps_find_consistent_after_a_long_chain() {
+        let mut sets = DisjointSet::new(10);
+        for i in 0..9 {
+            sets.union(i, i + 1);
+        }
+        let root = sets.find(0);
+        for i in 0..10 {
+            assert_eq!(sets.find(i), root);
+        }
+    }
+}